@@ -0,0 +1,212 @@
+//! On-disk incremental index so regenerating the graph after editing a
+//! single post doesn't require re-reading and re-tokenizing every file.
+
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::path::Path;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+/// A document-id bitset, serialized as 64-bit words. Stands in for a
+/// roaring bitmap: the corpora this tool indexes are small enough that
+/// a plain bitset is simpler and just as fast.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct DocBitmap {
+    bits: Vec<u64>,
+}
+
+impl DocBitmap {
+    pub fn set(&mut self, doc_id: usize) {
+        let word = doc_id / 64;
+        if word >= self.bits.len() {
+            self.bits.resize(word + 1, 0);
+        }
+        self.bits[word] |= 1 << (doc_id % 64);
+    }
+
+    pub fn unset(&mut self, doc_id: usize) {
+        let word = doc_id / 64;
+        if word < self.bits.len() {
+            self.bits[word] &= !(1 << (doc_id % 64));
+        }
+    }
+}
+
+/// A cached document: its stable id (so postings bits survive files
+/// being added/removed around it), content hash (for change
+/// detection) and its already-tokenized form, so unchanged files skip
+/// markdown parsing and tokenization on the next run.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct DocEntry {
+    pub id: usize,
+    pub hash: u64,
+    pub tokens: Vec<String>,
+}
+
+/// Computes a document's raw term occurrence counts (every token, not just
+/// vocabulary terms — needed to rank the vocabulary in the first
+/// place).
+pub fn term_counts(tokens: &[String]) -> HashMap<String, usize> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for t in tokens {
+        *counts.entry(t.clone()).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// Computes a document's windowed co-occurrence pair counts, restricted to
+/// tokens `in_vocab` accepts and with the window measured over the
+/// *vocab-filtered* sequence (non-vocab tokens are skipped rather than
+/// counted as part of the gap) — this is what makes `WINDOW` mean the
+/// same thing here as it does when the graph's edges are built, and
+/// keeps `co`/`postings` bounded to vocabulary-sized pairs instead of
+/// every n-gram the tokenizer produced.
+pub fn pair_counts(
+    tokens: &[String],
+    in_vocab: impl Fn(&str) -> bool,
+    window: usize,
+) -> HashMap<String, usize> {
+    let filtered: Vec<&String> = tokens.iter().filter(|t| in_vocab(t)).collect();
+
+    let mut pair_counts: HashMap<String, usize> = HashMap::new();
+    for (i, a) in filtered.iter().enumerate() {
+        let end = (i + window).min(filtered.len());
+        for b in &filtered[i + 1..end] {
+            *pair_counts.entry(co_key(a, b)).or_insert(0) += 1;
+        }
+    }
+    pair_counts
+}
+
+/// A document's vocab-filtered, deduplicated term set — used to
+/// decide which per-term posting bitmaps need `doc_id` set or unset.
+pub fn vocab_terms(tokens: &[String], in_vocab: impl Fn(&str) -> bool) -> HashSet<String> {
+    tokens.iter().filter(|t| in_vocab(t)).cloned().collect()
+}
+
+/// Canonical (order-independent) key for a co-occurring term pair, so
+/// `(a, b)` and `(b, a)` land on the same `co` entry.
+pub fn co_key(a: &str, b: &str) -> String {
+    if a <= b {
+        format!("{a}\u{0}{b}")
+    } else {
+        format!("{b}\u{0}{a}")
+    }
+}
+
+/// Splits a key produced by `co_key` back into its two terms.
+pub fn split_co_key(key: &str) -> (&str, &str) {
+    key.split_once('\u{0}').expect("co key always has a separator")
+}
+
+fn subtract(map: &mut HashMap<String, usize>, key: &str, amount: usize) {
+    if let Some(v) = map.get_mut(key) {
+        *v = v.saturating_sub(amount);
+        if *v == 0 {
+            map.remove(key);
+        }
+    }
+}
+
+/// Persisted alongside `graph.json`: per-document cache entries, plus
+/// the corpus-wide token frequency, document frequency, co-occurrence
+/// and posting aggregates needed to build the graph. These are
+/// maintained incrementally (see `apply_terms`/`apply_pairs`) rather
+/// than recomputed from every document on every run.
+///
+/// `freq`/`doc_freq` cover every token the tokenizer produces, since
+/// the vocabulary ranking needs corpus-wide counts to pick its top-N.
+/// `co`/`postings`, by contrast, are scoped to whichever terms are in
+/// the *vocabulary at the time a document was last (re)indexed* —
+/// mirroring the vocab-filtered window the graph itself uses and
+/// keeping these two maps bounded instead of covering every n-gram.
+/// A term that later falls out of the vocabulary leaves a few stale
+/// entries behind until the documents that reference it are edited
+/// again; graph building filters `co`/`postings` against the current
+/// vocabulary anyway, so this is harmless, just not immediately swept.
+#[derive(Default, Serialize, Deserialize)]
+pub struct Index {
+    pub docs: HashMap<String, DocEntry>,
+    pub next_doc_id: usize,
+    /// Corpus-wide occurrence count per token.
+    pub freq: HashMap<String, usize>,
+    /// Number of documents each token appears in at least once.
+    pub doc_freq: HashMap<String, usize>,
+    /// Windowed co-occurrence count per vocabulary term pair, keyed by
+    /// `co_key`.
+    pub co: HashMap<String, usize>,
+    /// Per-vocabulary-term posting bitmap of the documents it appears
+    /// in.
+    pub postings: HashMap<String, DocBitmap>,
+}
+
+impl Index {
+    pub fn load(path: &Path) -> Index {
+        std::fs::read(path)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, serde_json::to_vec_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Removes a document's old term counts (if any — `None` for a
+    /// newly added document) from `freq`/`doc_freq` and adds its new
+    /// ones, so corpus-wide counts stay correct without rescanning
+    /// unchanged documents.
+    pub fn apply_terms(&mut self, old: Option<&HashMap<String, usize>>, new: &HashMap<String, usize>) {
+        if let Some(old) = old {
+            for (term, count) in old {
+                subtract(&mut self.freq, term, *count);
+                if !new.contains_key(term) {
+                    subtract(&mut self.doc_freq, term, 1);
+                }
+            }
+        }
+
+        for (term, count) in new {
+            *self.freq.entry(term.clone()).or_insert(0) += count;
+            let was_present = old.is_some_and(|o| o.contains_key(term));
+            if !was_present {
+                *self.doc_freq.entry(term.clone()).or_insert(0) += 1;
+            }
+        }
+    }
+
+    /// Removes a document's old vocab-filtered pair counts (if any)
+    /// from `co` and adds its new ones.
+    pub fn apply_pairs(&mut self, old: Option<&HashMap<String, usize>>, new: &HashMap<String, usize>) {
+        if let Some(old) = old {
+            for (key, count) in old {
+                subtract(&mut self.co, key, *count);
+            }
+        }
+        for (key, count) in new {
+            *self.co.entry(key.clone()).or_insert(0) += count;
+        }
+    }
+
+    /// Unsets `doc_id`'s bit for vocabulary terms it no longer
+    /// contains and sets it for ones it newly does.
+    pub fn apply_postings(&mut self, doc_id: usize, old: &HashSet<String>, new: &HashSet<String>) {
+        for term in old.difference(new) {
+            if let Some(bitmap) = self.postings.get_mut(term) {
+                bitmap.unset(doc_id);
+            }
+        }
+        for term in new.difference(old) {
+            self.postings.entry(term.clone()).or_default().set(doc_id);
+        }
+    }
+}
+
+/// Hashes file contents for change detection (not cryptographic).
+pub fn hash_bytes(bytes: &[u8]) -> u64 {
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    bytes.hash(&mut hasher);
+    hasher.finish()
+}
@@ -1,99 +1,269 @@
+use std::collections::HashSet;
 use std::path::PathBuf;
 
 use anyhow::{Context, Result};
 use fnv::FnvHashMap as Map;
 use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd};
-use regex::Regex;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::Path;
 use walkdir::WalkDir;
 
-#[derive(Clone, Serialize)]
-struct Node {
-    id: usize,
-    label: String,
+mod index;
+mod porter;
+mod query;
+mod tokenizer;
+use index::{DocEntry, Index};
+use tokenizer::{Tokenizer, TokenizerOptions};
+
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct Node {
+    pub(crate) id: usize,
+    pub(crate) label: String,
     count: usize,
     x: f32,
     y: f32,
+    community: usize,
 }
 
-#[derive(Clone, Serialize)]
-struct Edge {
-    source: usize,
-    target: usize,
-    weight: usize,
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct Edge {
+    pub(crate) source: usize,
+    pub(crate) target: usize,
+    pub(crate) weight: f32,
 }
 
-#[derive(Serialize)]
-struct Graph {
-    nodes: Vec<Node>,
-    edges: Vec<Edge>,
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum WeightMode {
+    /// Raw window co-occurrence counts.
+    Count,
+    /// Positive pointwise mutual information.
+    Pmi,
+    /// PMI normalized to [0, 1] by -log2(p(a,b)).
+    NormalizedPmi,
+}
+
+impl WeightMode {
+    fn from_flag(s: &str) -> Option<Self> {
+        match s {
+            "count" => Some(WeightMode::Count),
+            "pmi" => Some(WeightMode::Pmi),
+            "npmi" => Some(WeightMode::NormalizedPmi),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum VocabMode {
+    /// Rank terms by raw corpus-wide frequency.
+    Frequency,
+    /// Rank terms by TF-IDF so boilerplate words common to every post
+    /// are downweighted relative to terms characteristic of few posts.
+    TfIdf,
+}
+
+impl VocabMode {
+    fn from_flag(s: &str) -> Option<Self> {
+        match s {
+            "freq" => Some(VocabMode::Frequency),
+            "tfidf" => Some(VocabMode::TfIdf),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub(crate) struct Graph {
+    pub(crate) nodes: Vec<Node>,
+    pub(crate) edges: Vec<Edge>,
 }
 
 const WIDTH: f32 = 1200.0;
 const HEIGHT: f32 = 800.0;
-const MAX_NODES: usize = 30;
+const MAX_NODES: usize = 4000;
 const WINDOW: usize = 12;
+/// Barnes-Hut opening angle: a cell is treated as a single pseudo-node
+/// when its width over the distance to it is below this threshold.
+const BARNES_HUT_THETA: f32 = 0.5;
 
 fn main() -> Result<()> {
-    let root = std::env::args()
-        .nth(1)
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("query") {
+        return query::run(&args[1..]);
+    }
+
+    let mut positional = vec![];
+    let mut weight_mode = WeightMode::Count;
+    let mut vocab_mode = VocabMode::Frequency;
+    let mut tokenizer_opts = TokenizerOptions::default();
+    let mut stopword_path: Option<String> = None;
+    for arg in &args {
+        if let Some(flag) = arg.strip_prefix("--weight=") {
+            weight_mode = WeightMode::from_flag(flag)
+                .with_context(|| format!("unknown --weight mode {flag:?} (expected count|pmi|npmi)"))?;
+        } else if let Some(flag) = arg.strip_prefix("--vocab=") {
+            vocab_mode = VocabMode::from_flag(flag)
+                .with_context(|| format!("unknown --vocab mode {flag:?} (expected freq|tfidf)"))?;
+        } else if let Some(flag) = arg.strip_prefix("--ngrams=") {
+            let (min_n, max_n) = parse_ngram_range(flag)
+                .with_context(|| format!("invalid --ngrams range {flag:?} (expected e.g. 1-3)"))?;
+            tokenizer_opts.ngram_min = min_n;
+            tokenizer_opts.ngram_max = max_n;
+        } else if arg == "--stem" {
+            tokenizer_opts.stem = true;
+        } else if let Some(flag) = arg.strip_prefix("--stopwords=") {
+            stopword_path = Some(flag.to_string());
+        } else {
+            positional.push(arg.clone());
+        }
+    }
+    let root = positional
+        .first()
+        .cloned()
         .unwrap_or_else(|| "content/blog".into());
-    let outdir = std::env::args()
-        .nth(2)
+    let outdir = positional
+        .get(1)
+        .cloned()
         .unwrap_or_else(|| "static/graph".into());
     fs::create_dir_all(&outdir)?;
 
-    let posts = collect_markdown(&root);
-    let texts: Vec<String> = posts?
-        .iter()
-        .map(|p| md_to_text(p).unwrap_or_default())
-        .collect();
+    let stopwords = match &stopword_path {
+        Some(p) => Tokenizer::load_stopwords(Path::new(p))?,
+        None => tokenizer::default_stopwords(),
+    };
+    let tokenizer = Tokenizer::new(stopwords, tokenizer_opts);
+
+    // incremental index: reuse cached tokens for any file whose content
+    // hash hasn't changed since the last run. Token-level aggregates
+    // (freq/doc_freq) are maintained by subtracting a changed
+    // document's old contribution and adding its new one
+    // (Index::apply_terms) rather than rescanning the whole corpus, so
+    // only added/edited/removed files do any token-level work.
+    // Co-occurrence pairs and postings are windowed/filtered against
+    // *this run's* vocabulary (see below) so they mean the same thing
+    // they always have and stay bounded to vocabulary-sized pairs
+    // rather than every n-gram the tokenizer produced.
+    // Community detection and the force layout still run in full each
+    // time: both depend on the whole current graph's topology, not on
+    // any one document, so there's no equivalent per-document delta.
+    let index_path = Path::new(&outdir).join("index.json");
+    let mut cache = Index::load(&index_path);
+
+    struct ChangedDoc {
+        doc_id: usize,
+        old_tokens: Option<Vec<String>>,
+        new_tokens: Vec<String>,
+    }
+    let mut changed: Vec<ChangedDoc> = vec![];
+
+    let files = collect_markdown(&root)?;
+    let mut seen_paths: HashSet<String> = HashSet::with_capacity(files.len());
+    for p in &files {
+        let path_key = p.to_string_lossy().into_owned();
+        let bytes = fs::read(p).with_context(|| format!("reading {}", p.display()))?;
+        let hash = index::hash_bytes(&bytes);
+        seen_paths.insert(path_key.clone());
+
+        let existing = cache.docs.get(&path_key).cloned();
+        if existing.as_ref().is_some_and(|e| e.hash == hash) {
+            continue;
+        }
 
-    let stop = stopwords();
-    let reg_word = Regex::new(r"[A-Za-z0-9][A-Za-z0-9\-']+").unwrap();
+        let text = md_to_text(p).unwrap_or_default();
+        let tokens = tokenizer.tokenize(&text);
 
-    // tokenization + extract bigrams
-    let docs: Vec<Vec<String>> = texts
-        .iter()
-        .map(|txt| tokenize(&reg_word, txt, &stop))
-        .collect();
+        let doc_id = match &existing {
+            Some(e) => e.id,
+            None => {
+                let id = cache.next_doc_id;
+                cache.next_doc_id += 1;
+                id
+            }
+        };
+        let new_term_counts = index::term_counts(&tokens);
+        let old_term_counts = existing.as_ref().map(|e| index::term_counts(&e.tokens));
+        cache.apply_terms(old_term_counts.as_ref(), &new_term_counts);
+
+        changed.push(ChangedDoc {
+            doc_id,
+            old_tokens: existing.as_ref().map(|e| e.tokens.clone()),
+            new_tokens: tokens.clone(),
+        });
+        cache.docs.insert(path_key, DocEntry { id: doc_id, hash, tokens });
+    }
 
-    // token frequency
-    let mut freq: Map<String, usize> = Map::default();
-    for doc in &docs {
-        for word in doc {
-            *freq.entry(word.clone()).or_default() += 1;
+    // drop cache entries (and their term-count contributions) for
+    // files removed since the last run
+    let removed_paths: Vec<String> = cache
+        .docs
+        .keys()
+        .filter(|path| !seen_paths.contains(*path))
+        .cloned()
+        .collect();
+    for path in removed_paths {
+        if let Some(entry) = cache.docs.remove(&path) {
+            let old_term_counts = index::term_counts(&entry.tokens);
+            cache.apply_terms(Some(&old_term_counts), &std::collections::HashMap::new());
+            changed.push(ChangedDoc {
+                doc_id: entry.id,
+                old_tokens: Some(entry.tokens),
+                new_tokens: vec![],
+            });
         }
     }
 
-    // keep top MAX_NODES by frequency
-    let mut vocab: Vec<(String, usize)> = freq.into_iter().collect();
-    vocab.sort_by(|a, b| b.1.cmp(&a.1));
+    // keep top MAX_NODES by the selected ranking
+    let mut vocab: Vec<(String, usize)> = cache.freq.iter().map(|(w, &c)| (w.clone(), c)).collect();
+    match vocab_mode {
+        VocabMode::Frequency => vocab.sort_by_key(|&(_, count)| std::cmp::Reverse(count)),
+        VocabMode::TfIdf => {
+            let num_docs = cache.docs.len();
+            vocab.sort_by(|a, b| {
+                let score_a = tfidf_score(a.1, cache.doc_freq.get(&a.0).copied().unwrap_or(0), num_docs);
+                let score_b = tfidf_score(b.1, cache.doc_freq.get(&b.0).copied().unwrap_or(0), num_docs);
+                score_b.partial_cmp(&score_a).unwrap()
+            });
+        }
+    }
     vocab.truncate(MAX_NODES);
     let vocab_set: Map<String, usize> = vocab
         .iter()
         .enumerate()
         .map(|(i, (w, _))| (w.clone(), i))
         .collect();
+    let counts: Vec<usize> = vocab.iter().map(|&(_, count)| count).collect();
+
+    // now that this run's vocabulary is settled, bring co/postings up
+    // to date for the documents that actually changed, windowing over
+    // each document's vocab-filtered token sequence (skipping non-vocab
+    // tokens) the same way the graph's edges are defined below
+    let in_vocab = |t: &str| vocab_set.contains_key(t);
+    for doc in &changed {
+        let new_pairs = index::pair_counts(&doc.new_tokens, in_vocab, WINDOW);
+        let old_pairs = doc.old_tokens.as_ref().map(|t| index::pair_counts(t, in_vocab, WINDOW));
+        cache.apply_pairs(old_pairs.as_ref(), &new_pairs);
+
+        let new_vocab_terms = index::vocab_terms(&doc.new_tokens, in_vocab);
+        let old_vocab_terms = doc
+            .old_tokens
+            .as_ref()
+            .map(|t| index::vocab_terms(t, in_vocab))
+            .unwrap_or_default();
+        cache.apply_postings(doc.doc_id, &old_vocab_terms, &new_vocab_terms);
+    }
+
+    cache.save(&index_path)?;
 
-    // co-occurrence counts
+    // co-occurrence counts: pull the (term, term) pairs already
+    // maintained in the index, keeping only those between two terms
+    // that made the cut for this run's vocabulary
     let mut co: Map<(usize, usize), usize> = Map::default();
-    let mut counts: Vec<usize> = vec![0; vocab_set.len()];
-
-    for doc in &docs {
-        let idxs: Vec<usize> = doc
-            .iter()
-            .filter_map(|w| vocab_set.get(w).copied())
-            .collect();
-        for (i, &a) in idxs.iter().enumerate() {
-            counts[a] += 1;
-            let end = (i + WINDOW).min(idxs.len());
-            for &b in &idxs[i + 1..end] {
-                let (u, v) = if a < b { (a, b) } else { (b, a) };
-                *co.entry((u, v)).or_default() += 1;
-            }
+    for (key, &w) in &cache.co {
+        let (a, b) = index::split_co_key(key);
+        if let (Some(&ia), Some(&ib)) = (vocab_set.get(a), vocab_set.get(b)) {
+            let (u, v) = if ia < ib { (ia, ib) } else { (ib, ia) };
+            co.insert((u, v), w);
         }
     }
 
@@ -106,26 +276,38 @@ fn main() -> Result<()> {
             count: 0,
             x: 0.0,
             y: 0.0,
+            community: 0,
         });
     }
-    for (i, c) in counts.into_iter().enumerate() {
+    for (i, &c) in counts.iter().enumerate() {
         nodes[i].count = c;
     }
 
+    let total_pairs: usize = co.values().sum();
     let mut edges: Vec<Edge> = co
         .into_iter()
         .map(|((u, v), w)| Edge {
             source: u,
             target: v,
-            weight: w,
+            weight: edge_weight(weight_mode, w, total_pairs, counts[u], counts[v]),
         })
         .collect();
 
     // prune weak edges
-    edges.retain(|e| e.weight > 1);
-    edges.sort_by(|a, b| b.weight.cmp(&a.weight));
+    let min_weight = match weight_mode {
+        WeightMode::Count => 1.0,
+        WeightMode::Pmi | WeightMode::NormalizedPmi => 0.0,
+    };
+    edges.retain(|e| e.weight > min_weight);
+    edges.sort_by(|a, b| b.weight.partial_cmp(&a.weight).unwrap());
     edges.truncate(MAX_NODES * 6);
 
+    // community detection (Louvain-style modularity optimization)
+    let communities = detect_communities(nodes.len(), &edges);
+    for (node, community) in nodes.iter_mut().zip(communities) {
+        node.community = community;
+    }
+
     // layout (Fruchterman-Reingold)
     layout_fr(&mut nodes, &edges, WIDTH, HEIGHT);
 
@@ -146,6 +328,14 @@ fn main() -> Result<()> {
     Ok(())
 }
 
+/// Parses an `--ngrams` flag value like `"1-3"` into (min, max).
+fn parse_ngram_range(s: &str) -> Option<(usize, usize)> {
+    let (lo, hi) = s.split_once('-')?;
+    let lo: usize = lo.parse().ok()?;
+    let hi: usize = hi.parse().ok()?;
+    (lo >= 1 && lo <= hi).then_some((lo, hi))
+}
+
 fn collect_markdown(dir: &str) -> Result<Vec<PathBuf>> {
     let mut files = vec![];
     for e in WalkDir::new(dir).into_iter().filter_map(Result::ok) {
@@ -193,40 +383,270 @@ fn md_to_text(p: &Path) -> Result<String> {
     Ok(out)
 }
 
-fn tokenize(reg: &Regex, text: &str, stop: &std::collections::HashSet<String>) -> Vec<String> {
-    let tokens: Vec<String> = reg
-        .find_iter(&text.to_lowercase())
-        .map(|m| m.as_str().trim_matches('-').to_string())
-        .filter(|w| w.len() >= 3 && !stop.contains(w))
-        .collect();
+/// Scores a term by TF-IDF: total corpus frequency weighted by how few
+/// documents it appears in, so words common to every post are
+/// downweighted relative to terms characteristic of specific posts.
+fn tfidf_score(term_freq: usize, doc_freq: usize, num_docs: usize) -> f32 {
+    let idf = ((1.0 + num_docs as f32) / (1.0 + doc_freq as f32)).ln();
+    term_freq as f32 * idf
+}
 
-    // add bigrams
-    let mut grams = Vec::with_capacity(tokens.len() * 2);
-    for i in 0..tokens.len() {
-        grams.push(tokens[i].clone());
-        if i + 1 < tokens.len() {
-            let bi = format!("{} {}", tokens[i], tokens[i + 1]);
-            if !stop.contains(&tokens[i]) && !stop.contains(&tokens[i + 1]) {
-                grams.push(bi);
+/// Scores a co-occurrence edge under the given `mode`. `co` is the raw
+/// window co-occurrence count, `total_pairs` the sum of all edge counts
+/// (`N`), and `count_a`/`count_b` the per-term occurrence counts.
+fn edge_weight(
+    mode: WeightMode,
+    co: usize,
+    total_pairs: usize,
+    count_a: usize,
+    count_b: usize,
+) -> f32 {
+    match mode {
+        WeightMode::Count => co as f32,
+        WeightMode::Pmi | WeightMode::NormalizedPmi => {
+            let p_joint = co as f32 / total_pairs as f32;
+            let pmi = ((co as f32 * total_pairs as f32) / (count_a as f32 * count_b as f32))
+                .log2()
+                .max(0.0);
+            if mode == WeightMode::NormalizedPmi {
+                let denom = -p_joint.log2();
+                if p_joint >= 1.0 {
+                    // The only co-occurring pair in the corpus: -log2(1) is
+                    // 0, but that's a maximally informative pair, not an
+                    // uninformative one, so treat it as fully normalized
+                    // rather than falling through to a weight of 0 (which
+                    // `min_weight` pruning would then drop as an edge).
+                    1.0
+                } else if denom > 0.0 {
+                    (pmi / denom).clamp(0.0, 1.0)
+                } else {
+                    0.0
+                }
+            } else {
+                pmi
+            }
+        }
+    }
+}
+
+/// Assigns each node a community id via one level of Louvain-style
+/// modularity optimization: every node starts in its own community,
+/// then nodes are repeatedly moved into whichever neighboring
+/// community yields the largest modularity gain
+/// `dQ = k_i_in/m - sigma_tot*k_i/(2*m^2)`, where `m` is total edge
+/// weight, `k_i` is node i's weighted degree, `k_i_in` the weight from
+/// i into the candidate community and `sigma_tot` that community's total
+/// degree. Stops when a full pass over all nodes makes no move.
+fn detect_communities(n: usize, edges: &[Edge]) -> Vec<usize> {
+    let mut adj: Vec<Vec<(usize, f64)>> = vec![vec![]; n];
+    let mut m = 0.0f64;
+    for e in edges {
+        let w = e.weight.max(0.0) as f64;
+        if w == 0.0 {
+            continue;
+        }
+        adj[e.source].push((e.target, w));
+        adj[e.target].push((e.source, w));
+        m += w;
+    }
+    if m == 0.0 {
+        return (0..n).collect();
+    }
+
+    let k: Vec<f64> = adj.iter().map(|nb| nb.iter().map(|&(_, w)| w).sum()).collect();
+    let mut community: Vec<usize> = (0..n).collect();
+    let mut sigma_tot: Vec<f64> = k.clone();
+
+    let mut improved = true;
+    while improved {
+        improved = false;
+        for i in 0..n {
+            let ki = k[i];
+            let ci = community[i];
+
+            let mut neighbor_weight: Map<usize, f64> = Map::default();
+            for &(j, w) in &adj[i] {
+                if j != i {
+                    *neighbor_weight.entry(community[j]).or_default() += w;
+                }
+            }
+
+            sigma_tot[ci] -= ki;
+
+            let mut best_c = ci;
+            let mut best_gain = 0.0f64;
+            for (&c, &k_i_in) in &neighbor_weight {
+                let gain = k_i_in / m - (sigma_tot[c] * ki) / (2.0 * m * m);
+                if gain > best_gain {
+                    best_gain = gain;
+                    best_c = c;
+                }
+            }
+
+            sigma_tot[best_c] += ki;
+            if best_c != ci {
+                community[i] = best_c;
+                improved = true;
             }
         }
     }
 
-    grams
+    // relabel to contiguous ids in order of first appearance
+    let mut relabel: Map<usize, usize> = Map::default();
+    community
+        .into_iter()
+        .map(|c| {
+            let next = relabel.len();
+            *relabel.entry(c).or_insert(next)
+        })
+        .collect()
 }
 
-fn stopwords() -> std::collections::HashSet<String> {
-    let list = [
-        "the", "and", "for", "with", "that", "this", "you", "your", "from", "are", "but", "was",
-        "were", "have", "has", "had", "not", "can", "will", "would", "could", "should", "about",
-        "into", "out", "over", "under", "between", "within", "without", "after", "before", "when",
-        "where", "how", "why", "what", "which", "while", "than", "then", "also", "just", "like",
-        "some", "more", "most", "much", "many", "each", "other", "another", "been", "being", "use",
-        "used", "using", "via", "a", "an", "in", "on", "of", "to", "as", "it", "is", "at", "by",
-        "or", "if", "we", "i",
-    ];
-
-    list.iter().map(|s| s.to_string()).collect()
+/// A node in the Barnes-Hut quadtree: either empty, a leaf holding one
+/// (possibly stacked) position, or an internal cell holding the
+/// center-of-mass and total mass of everything below it.
+enum Quad {
+    Empty,
+    Leaf {
+        x: f32,
+        y: f32,
+        mass: f32,
+    },
+    Internal {
+        cx: f32,
+        cy: f32,
+        mass: f32,
+        /// Side length of this cell's bounding square.
+        width: f32,
+        mid_x: f32,
+        mid_y: f32,
+        children: [Box<Quad>; 4],
+    },
+}
+
+impl Quad {
+    fn quadrant(mid_x: f32, mid_y: f32, x: f32, y: f32) -> usize {
+        match (x >= mid_x, y >= mid_y) {
+            (false, false) => 0,
+            (true, false) => 1,
+            (false, true) => 2,
+            (true, true) => 3,
+        }
+    }
+
+    fn child_bounds(min_x: f32, min_y: f32, size: f32, q: usize) -> (f32, f32, f32) {
+        let half = size / 2.0;
+        match q {
+            0 => (min_x, min_y, half),
+            1 => (min_x + half, min_y, half),
+            2 => (min_x, min_y + half, half),
+            3 => (min_x + half, min_y + half, half),
+            _ => unreachable!(),
+        }
+    }
+
+    fn insert(&mut self, min_x: f32, min_y: f32, size: f32, x: f32, y: f32, mass: f32) {
+        match self {
+            Quad::Empty => *self = Quad::Leaf { x, y, mass },
+            Quad::Leaf {
+                x: lx,
+                y: ly,
+                mass: lmass,
+            } => {
+                if (x - *lx).abs() < 1e-3 && (y - *ly).abs() < 1e-3 {
+                    *lmass += mass;
+                    return;
+                }
+                let (ox, oy, omass) = (*lx, *ly, *lmass);
+                let width = size;
+                let mid_x = min_x + width / 2.0;
+                let mid_y = min_y + width / 2.0;
+                let mut children = [
+                    Box::new(Quad::Empty),
+                    Box::new(Quad::Empty),
+                    Box::new(Quad::Empty),
+                    Box::new(Quad::Empty),
+                ];
+                let oq = Quad::quadrant(mid_x, mid_y, ox, oy);
+                let (ox0, oy0, osize) = Quad::child_bounds(min_x, min_y, size, oq);
+                children[oq].insert(ox0, oy0, osize, ox, oy, omass);
+                let nq = Quad::quadrant(mid_x, mid_y, x, y);
+                let (nx0, ny0, nsize) = Quad::child_bounds(min_x, min_y, size, nq);
+                children[nq].insert(nx0, ny0, nsize, x, y, mass);
+                let total = omass + mass;
+                *self = Quad::Internal {
+                    cx: (ox * omass + x * mass) / total,
+                    cy: (oy * omass + y * mass) / total,
+                    mass: total,
+                    width,
+                    mid_x,
+                    mid_y,
+                    children,
+                };
+            }
+            Quad::Internal {
+                cx,
+                cy,
+                mass: imass,
+                mid_x,
+                mid_y,
+                children,
+                ..
+            } => {
+                let total = *imass + mass;
+                *cx = (*cx * *imass + x * mass) / total;
+                *cy = (*cy * *imass + y * mass) / total;
+                *imass = total;
+                let q = Quad::quadrant(*mid_x, *mid_y, x, y);
+                let (qx0, qy0, qsize) = Quad::child_bounds(min_x, min_y, size, q);
+                children[q].insert(qx0, qy0, qsize, x, y, mass);
+            }
+        }
+    }
+
+    /// Approximates the repulsive force on a point at `(qx, qy)` from
+    /// everything in this cell, using the same `k*k/dist` law as the
+    /// exact pairwise computation but collapsing any cell whose
+    /// `width / dist < theta` into one pseudo-node at its
+    /// center-of-mass, weighted by how many points it represents.
+    fn repulsion(&self, qx: f32, qy: f32, theta: f32, k2: f32) -> (f32, f32) {
+        match self {
+            Quad::Empty => (0.0, 0.0),
+            Quad::Leaf { x, y, mass } => {
+                let dx = qx - x;
+                let dy = qy - y;
+                let dist = (dx * dx + dy * dy).sqrt();
+                if dist < 1e-3 {
+                    // Same position as the query point: either itself, skip.
+                    return (0.0, 0.0);
+                }
+                let dist = dist.max(0.01);
+                let force = k2 / dist * mass;
+                (dx / dist * force, dy / dist * force)
+            }
+            Quad::Internal {
+                cx,
+                cy,
+                mass,
+                width,
+                children,
+                ..
+            } => {
+                let dx = qx - cx;
+                let dy = qy - cy;
+                let dist = (dx * dx + dy * dy).sqrt().max(0.01);
+                if width / dist < theta {
+                    let force = k2 / dist * mass;
+                    (dx / dist * force, dy / dist * force)
+                } else {
+                    children
+                        .iter()
+                        .map(|c| c.repulsion(qx, qy, theta, k2))
+                        .fold((0.0, 0.0), |(fx, fy), (cfx, cfy)| (fx + cfx, fy + cfy))
+                }
+            }
+        }
+    }
 }
 
 fn layout_fr(nodes: &mut [Node], edges: &[Edge], w: f32, h: f32) {
@@ -247,19 +667,16 @@ fn layout_fr(nodes: &mut [Node], edges: &[Edge], w: f32, h: f32) {
     for _ in 0..iterations {
         let mut disp = vec![(0.0f32, 0.0f32); nodes.len()];
 
-        // calculate repulsive forces
-        for i in 0..nodes.len() {
-            for j in i + 1..nodes.len() {
-                let dx = nodes[i].x - nodes[j].x;
-                let dy = nodes[i].y - nodes[j].y;
-                let dist = (dx * dx + dy * dy).sqrt().max(0.01);
-                let force = (k * k) / dist;
-                let (fx, fy) = (dx / dist * force, dy / dist * force);
-                disp[i].0 += fx;
-                disp[i].1 += fy;
-                disp[j].0 -= fx;
-                disp[j].1 -= fy;
-            }
+        // calculate repulsive forces via a Barnes-Hut quadtree
+        let size = w.max(h);
+        let mut tree = Quad::Empty;
+        for v in nodes.iter() {
+            tree.insert(0.0, 0.0, size, v.x, v.y, 1.0);
+        }
+        for (i, v) in nodes.iter().enumerate() {
+            let (fx, fy) = tree.repulsion(v.x, v.y, BARNES_HUT_THETA, k * k);
+            disp[i].0 += fx;
+            disp[i].1 += fy;
         }
 
         // calculate attractive forces
@@ -294,6 +711,12 @@ fn layout_fr(nodes: &mut [Node], edges: &[Edge], w: f32, h: f32) {
     }
 }
 
+/// Distinct fill colors cycled across communities.
+const COMMUNITY_COLORS: &[&str] = &[
+    "#3b82f6", "#ef4444", "#22c55e", "#f59e0b", "#a855f7", "#06b6d4", "#ec4899", "#84cc16",
+    "#f97316", "#6366f1",
+];
+
 fn render_svg(nodes: &[Node], edges: &[Edge], w: f32, h: f32) -> String {
     let mut s = String::new();
     s.push_str(&format!(
@@ -301,7 +724,6 @@ fn render_svg(nodes: &[Node], edges: &[Edge], w: f32, h: f32) -> String {
 <style>
 text {{ font: 12px system-ui, sans-serif; fill: #222; }}
 .line {{ stroke: #999; stroke-opacity: .6; }}
-.node {{ fill: #3b82f6; }}
 </style>
 <rect x="0" y="0" width="{w}" height="{h}" fill="white" />
 "#
@@ -309,7 +731,7 @@ text {{ font: 12px system-ui, sans-serif; fill: #222; }}
 
     for e in edges {
         let (a, b) = (&nodes[e.source], &nodes[e.target]);
-        let sw = (1.0 + (e.weight as f32).ln()).clamp(1.0, 6.0);
+        let sw = (1.0 + e.weight.max(0.01).ln()).clamp(1.0, 6.0);
         s.push_str(&format!(
             r#"<line class="line" x1="{:.1}" y1="{:.1}" x2="{:.1}" y2="{:.1}" stroke-width="{:.2}" />"#,
             a.x, a.y, b.x, b.y, sw
@@ -318,9 +740,10 @@ text {{ font: 12px system-ui, sans-serif; fill: #222; }}
 
     for n in nodes {
         let r = 4.0 + (n.count as f32).log2().max(0.0);
+        let color = COMMUNITY_COLORS[n.community % COMMUNITY_COLORS.len()];
         s.push_str(&format!(
-            r#"<circle class="node" cx="{:.1}" cy="{:.1}" r="{:.1}"/>"#,
-            n.x, n.y, r
+            r#"<circle cx="{:.1}" cy="{:.1}" r="{:.1}" fill="{}"/>"#,
+            n.x, n.y, r, color
         ));
         s.push_str(&format!(
             r#"<text x="{:.1}" y="{:.1}" dx="6" dy="4">{}</text>"#,
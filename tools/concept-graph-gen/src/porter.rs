@@ -0,0 +1,349 @@
+//! The classic Porter stemming algorithm (Porter, 1980), applied to
+//! lowercase ASCII words so that e.g. "compute"/"computing"/"computed"
+//! collapse to the same stem.
+
+fn is_consonant(w: &[u8], i: usize) -> bool {
+    match w[i] {
+        b'a' | b'e' | b'i' | b'o' | b'u' => false,
+        b'y' => i == 0 || !is_consonant(w, i - 1),
+        _ => true,
+    }
+}
+
+/// The word's measure `m`: the number of vowel-consonant sequences
+/// after any initial consonant run, i.e. the word fits the pattern
+/// `[C](VC)^m[V]`.
+fn measure(w: &[u8]) -> usize {
+    let n = w.len();
+    let mut i = 0;
+    while i < n && is_consonant(w, i) {
+        i += 1;
+    }
+    let mut m = 0;
+    while i < n {
+        while i < n && !is_consonant(w, i) {
+            i += 1;
+        }
+        if i >= n {
+            break;
+        }
+        while i < n && is_consonant(w, i) {
+            i += 1;
+        }
+        m += 1;
+    }
+    m
+}
+
+fn contains_vowel(w: &[u8]) -> bool {
+    (0..w.len()).any(|i| !is_consonant(w, i))
+}
+
+fn ends_double_consonant(w: &[u8]) -> bool {
+    let n = w.len();
+    n >= 2 && w[n - 1] == w[n - 2] && is_consonant(w, n - 1)
+}
+
+/// Word ends consonant-vowel-consonant, where the final consonant is
+/// not w, x or y (Porter's `*o` condition).
+fn ends_cvc(w: &[u8]) -> bool {
+    let n = w.len();
+    n >= 3
+        && is_consonant(w, n - 3)
+        && !is_consonant(w, n - 2)
+        && is_consonant(w, n - 1)
+        && !matches!(w[n - 1], b'w' | b'x' | b'y')
+}
+
+fn has_suffix(w: &[u8], suffix: &str) -> bool {
+    let sfx = suffix.as_bytes();
+    w.len() >= sfx.len() && &w[w.len() - sfx.len()..] == sfx
+}
+
+/// If `w` ends with `suffix` and the stem left after removing it
+/// satisfies `cond`, replaces the suffix with `replacement` in place
+/// and returns `true`.
+fn replace_if(w: &mut Vec<u8>, suffix: &str, replacement: &str, cond: impl Fn(&[u8]) -> bool) -> bool {
+    if !has_suffix(w, suffix) {
+        return false;
+    }
+    let stem = w[..w.len() - suffix.len()].to_vec();
+    if !cond(&stem) {
+        return false;
+    }
+    let mut next = stem;
+    next.extend_from_slice(replacement.as_bytes());
+    *w = next;
+    true
+}
+
+/// Applies the rule for the *first* suffix in `rules` that matches the
+/// end of `w` (a suffix must be listed before any shorter suffix it
+/// ends with). Per Porter's
+/// algorithm only one rule may ever apply per step: if the matching
+/// suffix's condition fails, the word is left unmodified rather than
+/// falling through to a shorter suffix that also happens to match
+/// (e.g. "ational" also ends in "tional", "ement" also ends in "ment"
+/// and "ent").
+fn apply_first_matching(w: &mut Vec<u8>, rules: &[(&str, &str)], cond: impl Fn(&[u8]) -> bool) {
+    for (suffix, replacement) in rules {
+        if has_suffix(w, suffix) {
+            replace_if(w, suffix, replacement, &cond);
+            return;
+        }
+    }
+}
+
+fn step1a(w: &mut Vec<u8>) {
+    if replace_if(w, "sses", "ss", |_| true) {
+        return;
+    }
+    if replace_if(w, "ies", "i", |_| true) {
+        return;
+    }
+    if replace_if(w, "ss", "ss", |_| true) {
+        return;
+    }
+    replace_if(w, "s", "", |_| true);
+}
+
+fn step1b(w: &mut Vec<u8>) {
+    // EED/ED/ING are mutually exclusive by longest-match: once the word
+    // is recognized as ending in EED, that's the only rule that applies
+    // for this step, whether or not its `measure > 0` gate holds — it
+    // must not fall through and also try stripping ED/ING.
+    if has_suffix(w, "eed") {
+        replace_if(w, "eed", "ee", |stem| measure(stem) > 0);
+        return;
+    }
+
+    let shortened = if has_suffix(w, "ed") {
+        replace_if(w, "ed", "", contains_vowel)
+    } else if has_suffix(w, "ing") {
+        replace_if(w, "ing", "", contains_vowel)
+    } else {
+        false
+    };
+    if !shortened {
+        return;
+    }
+    if replace_if(w, "at", "ate", |_| true)
+        || replace_if(w, "bl", "ble", |_| true)
+        || replace_if(w, "iz", "ize", |_| true)
+    {
+        return;
+    }
+    if ends_double_consonant(w) && !matches!(w[w.len() - 1], b'l' | b's' | b'z') {
+        w.pop();
+    } else if measure(w) == 1 && ends_cvc(w) {
+        w.push(b'e');
+    }
+}
+
+fn step1c(w: &mut Vec<u8>) {
+    replace_if(w, "y", "i", contains_vowel);
+}
+
+fn step2(w: &mut Vec<u8>) {
+    const RULES: &[(&str, &str)] = &[
+        ("ational", "ate"),
+        ("tional", "tion"),
+        ("enci", "ence"),
+        ("anci", "ance"),
+        ("izer", "ize"),
+        ("abli", "able"),
+        ("alli", "al"),
+        ("entli", "ent"),
+        ("eli", "e"),
+        ("ousli", "ous"),
+        ("ization", "ize"),
+        ("ation", "ate"),
+        ("ator", "ate"),
+        ("alism", "al"),
+        ("iveness", "ive"),
+        ("fulness", "ful"),
+        ("ousness", "ous"),
+        ("aliti", "al"),
+        ("iviti", "ive"),
+        ("biliti", "ble"),
+    ];
+    apply_first_matching(w, RULES, |stem| measure(stem) > 0);
+}
+
+fn step3(w: &mut Vec<u8>) {
+    const RULES: &[(&str, &str)] = &[
+        ("icate", "ic"),
+        ("ative", ""),
+        ("alize", "al"),
+        ("iciti", "ic"),
+        ("ical", "ic"),
+        ("ful", ""),
+        ("ness", ""),
+    ];
+    apply_first_matching(w, RULES, |stem| measure(stem) > 0);
+}
+
+fn step4(w: &mut Vec<u8>) {
+    // In Porter's table order; "ion" sits between "ent" and
+    // "ou" per Porter's table and carries its own extra condition, so
+    // it's handled alongside the rest rather than tried separately
+    // after the loop (which would let a shorter suffix match even
+    // when "ion" was the true longest match for the word).
+    const RULES: &[&str] = &[
+        "al", "ance", "ence", "er", "ic", "able", "ible", "ant", "ement", "ment", "ent", "ion",
+        "ou", "ism", "ate", "iti", "ous", "ive", "ize",
+    ];
+    for suffix in RULES {
+        if has_suffix(w, suffix) {
+            if *suffix == "ion" {
+                replace_if(w, suffix, "", |stem| {
+                    measure(stem) > 1 && matches!(stem.last(), Some(b's') | Some(b't'))
+                });
+            } else {
+                replace_if(w, suffix, "", |stem| measure(stem) > 1);
+            }
+            return;
+        }
+    }
+}
+
+fn step5a(w: &mut Vec<u8>) {
+    if replace_if(w, "e", "", |stem| measure(stem) > 1) {
+        return;
+    }
+    replace_if(w, "e", "", |stem| measure(stem) == 1 && !ends_cvc(stem));
+}
+
+fn step5b(w: &mut Vec<u8>) {
+    if w.len() >= 2 && w[w.len() - 1] == b'l' && ends_double_consonant(w) && measure(w) > 1 {
+        w.pop();
+    }
+}
+
+/// Reduces a lowercase word to its Porter stem. Words of length 2 or
+/// less, and input that is not entirely lowercase ASCII letters, are
+/// returned unchanged.
+pub fn stem(word: &str) -> String {
+    if word.len() <= 2 || !word.bytes().all(|b| b.is_ascii_lowercase()) {
+        return word.to_string();
+    }
+
+    let mut w = word.as_bytes().to_vec();
+    step1a(&mut w);
+    step1b(&mut w);
+    step1c(&mut w);
+    step2(&mut w);
+    step3(&mut w);
+    step4(&mut w);
+    step5a(&mut w);
+    step5b(&mut w);
+
+    String::from_utf8(w).unwrap_or_else(|_| word.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::stem;
+
+    /// Known input/output pairs from Porter's own reference vocabulary,
+    /// covering each step (plurals and participles in 1a/1b, y->i in
+    /// 1c, the derivational suffixes in 2/3, the longer suffixes in 4,
+    /// and the trailing-e/double-l cleanup in 5a/5b).
+    const CASES: &[(&str, &str)] = &[
+        ("caresses", "caress"),
+        ("ponies", "poni"),
+        ("ties", "ti"),
+        ("caress", "caress"),
+        ("cats", "cat"),
+        ("feed", "feed"),
+        ("speed", "speed"),
+        ("agreed", "agre"),
+        ("plastered", "plaster"),
+        ("bled", "bled"),
+        ("motoring", "motor"),
+        ("sing", "sing"),
+        ("conflated", "conflat"),
+        ("troubled", "troubl"),
+        ("sized", "size"),
+        ("hopping", "hop"),
+        ("tanned", "tan"),
+        ("falling", "fall"),
+        ("hissing", "hiss"),
+        ("fizzed", "fizz"),
+        ("failing", "fail"),
+        ("filing", "file"),
+        ("happy", "happi"),
+        ("sky", "sky"),
+        ("relational", "relat"),
+        ("conditional", "condit"),
+        ("rational", "ration"),
+        ("valenci", "valenc"),
+        ("hesitanci", "hesit"),
+        ("digitizer", "digit"),
+        ("conformabli", "conform"),
+        ("radicalli", "radic"),
+        ("differentli", "differ"),
+        ("vileli", "vile"),
+        ("analogousli", "analog"),
+        ("vietnamization", "vietnam"),
+        ("predication", "predic"),
+        ("operator", "oper"),
+        ("feudalism", "feudal"),
+        ("decisiveness", "decis"),
+        ("hopefulness", "hope"),
+        ("callousness", "callous"),
+        ("formaliti", "formal"),
+        ("sensitiviti", "sensit"),
+        ("sensibiliti", "sensibl"),
+        ("triplicate", "triplic"),
+        ("formative", "form"),
+        ("formalize", "formal"),
+        ("electriciti", "electr"),
+        ("electrical", "electr"),
+        ("hopeful", "hope"),
+        ("goodness", "good"),
+        ("revival", "reviv"),
+        ("allowance", "allow"),
+        ("inference", "infer"),
+        ("airliner", "airlin"),
+        ("gyroscopic", "gyroscop"),
+        ("adjustable", "adjust"),
+        ("defensible", "defens"),
+        ("irritant", "irrit"),
+        ("replacement", "replac"),
+        ("adjustment", "adjust"),
+        ("dependent", "depend"),
+        ("adoption", "adopt"),
+        ("homologou", "homolog"),
+        ("communism", "commun"),
+        ("activate", "activ"),
+        ("angulariti", "angular"),
+        ("homologous", "homolog"),
+        ("effective", "effect"),
+        ("bowdlerize", "bowdler"),
+        ("probate", "probat"),
+        ("rate", "rate"),
+        ("cease", "ceas"),
+        ("controll", "control"),
+        ("roll", "roll"),
+    ];
+
+    #[test]
+    fn matches_porter_reference_vocabulary() {
+        for &(input, expected) in CASES {
+            assert_eq!(stem(input), expected, "stem({input:?})");
+        }
+    }
+
+    #[test]
+    fn short_words_are_left_alone() {
+        assert_eq!(stem("a"), "a");
+        assert_eq!(stem("is"), "is");
+    }
+
+    #[test]
+    fn non_lowercase_ascii_is_left_alone() {
+        assert_eq!(stem("Feed"), "Feed");
+        assert_eq!(stem("café"), "café");
+    }
+}
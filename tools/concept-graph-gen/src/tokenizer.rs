@@ -0,0 +1,99 @@
+//! Turns raw post text into graph terms: word extraction, stopword
+//! filtering, optional Porter stemming, and configurable n-gram
+//! generation (unigrams through trigrams).
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use regex::Regex;
+
+use crate::porter;
+
+/// Default English stopword list, used when no `--stopwords` file is given.
+pub fn default_stopwords() -> HashSet<String> {
+    let list = [
+        "the", "and", "for", "with", "that", "this", "you", "your", "from", "are", "but", "was",
+        "were", "have", "has", "had", "not", "can", "will", "would", "could", "should", "about",
+        "into", "out", "over", "under", "between", "within", "without", "after", "before", "when",
+        "where", "how", "why", "what", "which", "while", "than", "then", "also", "just", "like",
+        "some", "more", "most", "much", "many", "each", "other", "another", "been", "being", "use",
+        "used", "using", "via", "a", "an", "in", "on", "of", "to", "as", "it", "is", "at", "by",
+        "or", "if", "we", "i",
+    ];
+
+    list.iter().map(|s| s.to_string()).collect()
+}
+
+pub struct TokenizerOptions {
+    /// Smallest n-gram length to emit (1 = unigrams).
+    pub ngram_min: usize,
+    /// Largest n-gram length to emit (e.g. 2 = up through bigrams).
+    pub ngram_max: usize,
+    /// Collapse words to their Porter stem (e.g. compute/computing/computed).
+    pub stem: bool,
+}
+
+impl Default for TokenizerOptions {
+    fn default() -> Self {
+        TokenizerOptions {
+            ngram_min: 1,
+            ngram_max: 2,
+            stem: false,
+        }
+    }
+}
+
+pub struct Tokenizer {
+    word_re: Regex,
+    stopwords: HashSet<String>,
+    options: TokenizerOptions,
+}
+
+impl Tokenizer {
+    pub fn new(stopwords: HashSet<String>, options: TokenizerOptions) -> Self {
+        Tokenizer {
+            word_re: Regex::new(r"[A-Za-z0-9][A-Za-z0-9\-']+").unwrap(),
+            stopwords,
+            options,
+        }
+    }
+
+    /// Loads a newline-separated stopword list, skipping blank lines
+    /// and lines starting with `#`.
+    pub fn load_stopwords(path: &Path) -> Result<HashSet<String>> {
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("reading stopword file {}", path.display()))?;
+        Ok(text
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty() && !l.starts_with('#'))
+            .map(str::to_string)
+            .collect())
+    }
+
+    pub fn tokenize(&self, text: &str) -> Vec<String> {
+        const MIN_LEN: usize = 3;
+
+        let unigrams: Vec<String> = self
+            .word_re
+            .find_iter(&text.to_lowercase())
+            .map(|m| m.as_str().trim_matches('-').to_string())
+            .filter(|w| w.len() >= MIN_LEN && !self.stopwords.contains(w))
+            .map(|w| if self.options.stem { porter::stem(&w) } else { w })
+            .collect();
+
+        let max_n = self.options.ngram_max.max(self.options.ngram_min);
+        let mut grams = Vec::with_capacity(unigrams.len() * (max_n - self.options.ngram_min + 1));
+        for n in self.options.ngram_min..=max_n {
+            if n == 0 || n > unigrams.len() {
+                continue;
+            }
+            for window in unigrams.windows(n) {
+                grams.push(window.join(" "));
+            }
+        }
+        grams
+    }
+}